@@ -0,0 +1,22 @@
+use super::*;
+
+/// Sentinel asset id for the chain's built-in currency. Fee and coinbase subsidy
+/// accounting apply only to this asset; for every other asset a transaction's outputs
+/// may not exceed its inputs, and any surplus is not counted toward a fee.
+pub fn native_asset() -> Hash {
+    vec![0; 32]
+}
+
+/// Introduces a new asset onto the chain, or mints more supply of one already marked
+/// reissuable. Admitted alongside the block that carries it through
+/// `Blockchain::update_with_block`, rather than applied as a standalone call -- unlike
+/// ordinary transfers of an asset that already exists, which flow through regular
+/// `Transaction`s within the block itself.
+#[derive(Clone)]
+pub struct IssuanceTransaction {
+    pub asset_id: Hash,
+    pub outputs: Vec<Output>,
+    /// Whether later `IssuanceTransaction`s for this `asset_id` may mint further supply.
+    /// `false` makes this a one-time, fixed-supply issuance.
+    pub reissuable: bool,
+}
@@ -0,0 +1,176 @@
+use super::*;
+
+/// Builds fee-maximizing block templates out of a `Mempool`, modeled on the
+/// greedy, fee-rate-ordered selection a miner's block assembler performs.
+pub struct BlockAssembler {
+    /// Cap on the total serialized size (in bytes) of the transactions a template may hold.
+    pub max_block_weight: usize,
+    /// Block subsidy paid to the coinbase output, on top of collected fees.
+    pub block_subsidy: u64,
+}
+
+impl BlockAssembler {
+    pub fn new(max_block_weight: usize, block_subsidy: u64) -> Self {
+        BlockAssembler {
+            max_block_weight,
+            block_subsidy,
+        }
+    }
+
+    /// Greedily selects mempool transactions ordered by fee-rate (fee per serialized byte)
+    /// until `max_block_weight` is reached, then builds a coinbase paying `coinbase_addr`
+    /// the subsidy plus the collected fees. Returns a block template sitting on top of
+    /// `prev_block`; the caller is still responsible for mining it.
+    pub fn assemble_block(
+        &self,
+        mempool: &Mempool,
+        coinbase_addr: Address,
+        coinbase_locking_script: Hash,
+        prev_block: &Block,
+        difficulty: u128,
+        timestamp: u128,
+    ) -> Block {
+        let mut candidates: Vec<&Transaction> = mempool.transactions().iter().collect();
+        candidates.sort_by(|a, b| fee_rate(b).cmp(&fee_rate(a)));
+
+        let mut selected = vec![];
+        let mut weight = 0;
+        let mut total_fee = 0;
+
+        for transaction in candidates {
+            let transaction_weight = transaction.bytes().len();
+            if weight + transaction_weight > self.max_block_weight {
+                continue;
+            }
+
+            weight += transaction_weight;
+            total_fee +=
+                native_value(&transaction.inputs).saturating_sub(native_value(&transaction.outputs));
+            selected.push(transaction.clone());
+        }
+
+        let coinbase = Transaction {
+            inputs: vec![],
+            outputs: vec![Output {
+                to_addr: coinbase_addr,
+                value: self.block_subsidy + total_fee,
+                asset_id: native_asset(),
+                locking_script: coinbase_locking_script,
+                unlocking_pubkey: vec![],
+                unlocking_signature: vec![],
+            }],
+        };
+
+        let mut transactions = vec![coinbase];
+        transactions.extend(selected);
+
+        Block::new(
+            prev_block.index + 1,
+            timestamp,
+            prev_block.hash.clone(),
+            transactions,
+            difficulty,
+        )
+    }
+}
+
+/// Fee paid per serialized byte, in the native asset -- the ordering a greedy block
+/// assembler maximizes. Fees are only ever collected in the native asset; a surplus in
+/// any other asset is not a fee (see `Blockchain::apply_transactions`).
+fn fee_rate(transaction: &Transaction) -> u64 {
+    let weight = transaction.bytes().len().max(1) as u64;
+    native_value(&transaction.inputs).saturating_sub(native_value(&transaction.outputs)) / weight
+}
+
+fn native_value(outputs: &[Output]) -> u64 {
+    let native = native_asset();
+    outputs
+        .iter()
+        .filter(|output| output.asset_id == native)
+        .map(|output| output.value)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr() -> Address {
+        "test-address".to_owned()
+    }
+
+    fn output(value: u64, asset_id: Hash) -> Output {
+        Output {
+            to_addr: addr(),
+            value,
+            asset_id,
+            locking_script: vec![0; 32],
+            unlocking_pubkey: vec![],
+            unlocking_signature: vec![],
+        }
+    }
+
+    fn genesis() -> Block {
+        Block::new(0, 1, vec![0; 32], vec![], u128::MAX)
+    }
+
+    #[test]
+    fn native_value_ignores_other_assets() {
+        let outputs = vec![
+            output(100, native_asset()),
+            output(30, native_asset()),
+            output(999, vec![9; 32]),
+        ];
+
+        assert_eq!(native_value(&outputs), 130);
+    }
+
+    #[test]
+    fn fee_rate_is_the_native_surplus_per_serialized_byte() {
+        let transaction = Transaction {
+            inputs: vec![output(150, native_asset())],
+            outputs: vec![output(100, native_asset())],
+        };
+
+        let expected_weight = transaction.bytes().len().max(1) as u64;
+        assert_eq!(fee_rate(&transaction), 50 / expected_weight);
+    }
+
+    #[test]
+    fn fee_rate_ignores_surplus_in_a_non_native_asset() {
+        let transaction = Transaction {
+            inputs: vec![output(150, vec![9; 32])],
+            outputs: vec![output(100, vec![9; 32])],
+        };
+
+        assert_eq!(fee_rate(&transaction), 0);
+    }
+
+    #[test]
+    fn assemble_block_builds_a_subsidy_only_coinbase_over_an_empty_mempool() {
+        let assembler = BlockAssembler::new(1_000_000, 50);
+        let mempool = Mempool::new();
+        let prev_block = genesis();
+
+        let block = assembler.assemble_block(
+            &mempool,
+            addr(),
+            vec![7; 32],
+            &prev_block,
+            u128::MAX,
+            2,
+        );
+
+        assert_eq!(block.index, prev_block.index + 1);
+        assert_eq!(block.prev_block_hash, prev_block.hash);
+        assert_eq!(block.transactions.len(), 1);
+
+        let coinbase = &block.transactions[0];
+        assert!(coinbase.inputs.is_empty());
+        assert_eq!(coinbase.outputs.len(), 1);
+        assert_eq!(coinbase.outputs[0].value, 50);
+        assert_eq!(coinbase.outputs[0].to_addr, addr());
+        assert_eq!(coinbase.outputs[0].asset_id, native_asset());
+        assert_eq!(coinbase.outputs[0].locking_script, vec![7; 32]);
+    }
+}
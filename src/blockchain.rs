@@ -1,5 +1,6 @@
+use crate::asset::{native_asset, IssuanceTransaction};
 use crate::block::check_difficulty;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use super::*;
 #[derive(Debug)]
@@ -10,12 +11,82 @@ pub enum BlockValidationError {
     MismatchedPreviousHash,
     InvalidGenesisBlockFormat,
     InvalidInput,
-    InsufficientInputValue,
     InvalidCoinbaseTransaction,
+    UnexpectedDifficulty,
+    UnknownParent,
+    ScriptVerificationFailed,
+    InvalidSnapshot,
+    AssetImbalance,
+    AssetAlreadyIssued,
 }
+
+/// Where a newly accepted block landed: the canonical chain, or a side branch that
+/// hasn't (yet) accumulated more work than the current main chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockLocation {
+    Main { height: u32 },
+    Side { height: u32 },
+}
+
+/// Number of blocks between difficulty retargets.
+const RETARGET_INTERVAL: u32 = 2016;
+/// Desired seconds between blocks.
+const TARGET_SECONDS_PER_BLOCK: u128 = 10 * 60;
+/// Desired wall-clock time for a full retarget interval.
+const TARGET_TIMESPAN: u128 = RETARGET_INTERVAL as u128 * TARGET_SECONDS_PER_BLOCK;
+
+/// A block accepted onto a side branch but not (yet) canonical. Keyed in
+/// `Blockchain::side_branches` by its own hash -- not just the branch's current tip --
+/// so a block that forks off an *earlier* block within an existing side branch (a
+/// competing fork within that branch, not just against the main chain) has a known
+/// parent to attach to instead of being wrongly rejected as `UnknownParent`.
+struct OffChainBlock {
+    block: Block,
+    fork_height: u32,
+    /// Main-chain outputs consumed by this block or any of its branch ancestors back
+    /// to the fork point -- excluded from the main chain's UTXO set at `fork_height`
+    /// when validating a sibling built on top of this one.
+    spent: HashSet<Hash>,
+    /// Outputs created by this block or any of its branch ancestors back to the fork
+    /// point that are still unspent along this same path.
+    created: HashSet<Hash>,
+    /// This branch's cumulative proof-of-work from the fork point through this block.
+    work: u128,
+    /// Issuances carried by this block, so they travel with it if it's later adopted
+    /// onto the main chain by a reorg, or displaced back onto a side branch by one.
+    issuances: Vec<IssuanceTransaction>,
+    /// Asset registry as it stands after applying every issuance from the fork point
+    /// through this block, mirroring `spent`/`created` for issuances instead of UTXOs --
+    /// without it, two blocks on the same still-uncommitted branch could each "first"
+    /// issue the same non-reissuable `asset_id`, since `self.asset_registry` (the
+    /// committed main-chain state) wouldn't see either until the branch won a reorg.
+    asset_overlay: HashMap<Hash, AssetRecord>,
+}
+
+/// An asset's issuance history: whether further reissuances are allowed, and how many
+/// issuances have contributed to it, so unwinding the most recent one during a reorg
+/// can tell whether it was the asset's only issuance (in which case the registry entry
+/// itself is undone) or one of several (in which case earlier ones are still in effect).
+#[derive(Clone)]
+struct AssetRecord {
+    reissuable: bool,
+    issuance_count: u32,
+}
+
 pub struct Blockchain {
     pub blocks: Vec<Block>,
     unspent_outputs: HashSet<Hash>,
+    side_branches: HashMap<Hash, OffChainBlock>,
+    /// Height of the block at `self.blocks[0]`. Zero for a chain built up from genesis;
+    /// set to a snapshot's height when the chain instead resumed from a UTXO snapshot,
+    /// so `self.blocks` only ever holds the history that was actually replayed.
+    height_offset: u32,
+    /// Tracks which asset ids have been issued, keyed by `asset_id`.
+    asset_registry: HashMap<Hash, AssetRecord>,
+    /// Issuances introduced by each main-chain block, keyed by its hash, so a reorg
+    /// that disconnects the block can undo them and one that reconnects it can redo
+    /// them -- `Block` itself has no field to carry an issuance directly.
+    issuances_by_block: HashMap<Hash, Vec<IssuanceTransaction>>,
 }
 
 impl Blockchain {
@@ -23,80 +94,1066 @@ impl Blockchain {
         Blockchain {
             blocks: vec![],
             unspent_outputs: HashSet::new(),
+            side_branches: HashMap::new(),
+            height_offset: 0,
+            asset_registry: HashMap::new(),
+            issuances_by_block: HashMap::new(),
+        }
+    }
+
+    /// The current UTXO set, for a caller that wants to check or build against it
+    /// without going through `update_with_block` -- e.g. a `Mempool` validating
+    /// incoming transactions, or a `BlockAssembler` picking ones to template.
+    pub fn unspent_outputs(&self) -> &HashSet<Hash> {
+        &self.unspent_outputs
+    }
+
+    /// Checks `issuances` against each asset's issuance history without mutating it,
+    /// walking them in order so a block may legally introduce an asset and reissue it
+    /// again later in the same block. `branch_overlay`, when given, is consulted ahead
+    /// of `self.asset_registry` -- it carries the not-yet-committed issuance history of
+    /// the side branch this block extends, so a branch can't "first" issue the same
+    /// non-reissuable `asset_id` twice just because neither block has reached the
+    /// committed registry yet.
+    fn validate_issuances(
+        &self,
+        issuances: &[IssuanceTransaction],
+        branch_overlay: Option<&HashMap<Hash, AssetRecord>>,
+    ) -> Result<(), BlockValidationError> {
+        let mut pending: HashMap<&Hash, bool> = HashMap::new();
+
+        for issuance in issuances {
+            let reissuable = pending.get(&issuance.asset_id).copied().or_else(|| {
+                branch_overlay
+                    .and_then(|overlay| overlay.get(&issuance.asset_id))
+                    .or_else(|| self.asset_registry.get(&issuance.asset_id))
+                    .map(|record| record.reissuable)
+            });
+            if reissuable == Some(false) {
+                return Err(BlockValidationError::AssetAlreadyIssued);
+            }
+            pending.insert(&issuance.asset_id, issuance.reissuable);
+        }
+
+        Ok(())
+    }
+
+    /// Applies `issuances` to a branch-local copy of the asset registry, the same way
+    /// `commit_issuances` applies them to `self.asset_registry`, but without touching
+    /// any chain state -- used to build the `asset_overlay` a side branch's next block
+    /// is validated against.
+    fn apply_issuances_to_overlay(
+        overlay: &mut HashMap<Hash, AssetRecord>,
+        issuances: &[IssuanceTransaction],
+    ) {
+        for issuance in issuances {
+            let record = overlay.entry(issuance.asset_id.clone()).or_insert(AssetRecord {
+                reissuable: issuance.reissuable,
+                issuance_count: 0,
+            });
+            record.reissuable = issuance.reissuable;
+            record.issuance_count += 1;
+        }
+    }
+
+    /// Applies already-validated `issuances` -- minting their outputs and updating the
+    /// asset registry -- and files them under `block_hash` so a later reorg can find
+    /// them again to unwind or redo.
+    fn commit_issuances(&mut self, block_hash: Hash, issuances: Vec<IssuanceTransaction>) {
+        for issuance in &issuances {
+            let record = self
+                .asset_registry
+                .entry(issuance.asset_id.clone())
+                .or_insert(AssetRecord {
+                    reissuable: issuance.reissuable,
+                    issuance_count: 0,
+                });
+            record.reissuable = issuance.reissuable;
+            record.issuance_count += 1;
+
+            self.unspent_outputs
+                .extend(issuance.outputs.iter().map(|output| output.hash()));
+        }
+
+        if !issuances.is_empty() {
+            self.issuances_by_block.insert(block_hash, issuances);
+        }
+    }
+
+    /// Reverses `commit_issuances` for the block at `block_hash`: drops its minted
+    /// outputs from the UTXO set, and removes an asset's registry entry entirely once
+    /// its last remaining issuance is undone. Returns the issuances that were removed
+    /// so a reorg can re-file them if the block ends up back on a side branch.
+    fn unwind_issuances(&mut self, block_hash: &Hash) -> Vec<IssuanceTransaction> {
+        let issuances = self.issuances_by_block.remove(block_hash).unwrap_or_default();
+
+        for issuance in issuances.iter().rev() {
+            let minted: HashSet<Hash> = issuance.outputs.iter().map(|output| output.hash()).collect();
+            self.unspent_outputs.retain(|output| !minted.contains(output));
+
+            if let Some(record) = self.asset_registry.get_mut(&issuance.asset_id) {
+                record.issuance_count -= 1;
+                if record.issuance_count == 0 {
+                    self.asset_registry.remove(&issuance.asset_id);
+                }
+            }
         }
+
+        issuances
+    }
+
+    /// Maps an absolute chain height to its index into `self.blocks`.
+    fn relative_index(&self, height: u32) -> usize {
+        (height - self.height_offset) as usize
     }
 
-    pub fn update_with_block(&mut self, block: Block) -> Result<(), BlockValidationError> {
-        let i = self.blocks.len();
-        let prev_block = &self.blocks[i - 1];
+    /// Walks back from `hash` to the block at `height`, following a side branch's own
+    /// ancestry through `side_branches` before falling back to `self.blocks` once the
+    /// walk reaches a block already on the main chain. Lets a side branch's retarget
+    /// window (see `expected_difficulty`) reach into blocks that aren't canonical yet,
+    /// instead of a lookup that only understands `self.blocks` and would otherwise read
+    /// the wrong chain's history -- or panic -- once a block's ancestry crosses the
+    /// fork point.
+    fn ancestor_block(&self, hash: &Hash, height: u32) -> Option<Block> {
+        if let Some(off_chain) = self.side_branches.get(hash) {
+            return if off_chain.block.index == height {
+                Some(off_chain.block.clone())
+            } else {
+                self.ancestor_block(&off_chain.block.prev_block_hash, height)
+            };
+        }
+
+        let main_height = self.height_of(hash)?;
+        if height < self.height_offset || height > main_height {
+            return None;
+        }
+        Some(self.blocks[self.relative_index(height)].clone())
+    }
+
+    /// Computes the difficulty the block extending `parent` at `index` must present,
+    /// retargeting every `RETARGET_INTERVAL` blocks to keep the average time between
+    /// blocks close to `TARGET_SECONDS_PER_BLOCK`, analogous to Bitcoin's "expected
+    /// nbits" check. Reaches the retarget window's start through `ancestor_block`
+    /// rather than indexing `self.blocks` directly, so a side branch is held to the
+    /// same retargeting rule as the main chain, computed from its own ancestry rather
+    /// than the main chain's -- a branch mined start-to-finish at a single
+    /// never-retargeted difficulty is no longer able to cross a retarget boundary
+    /// unnoticed and later win a reorg. Falls back to `parent`'s difficulty if the full
+    /// retarget window predates `height_offset`, i.e. a snapshot-resumed chain hasn't
+    /// replayed it. Returns `None` if the window's start isn't reachable from `parent`
+    /// at all, which should never happen for a block admitted through
+    /// `update_with_block`'s own bookkeeping; callers treat it as a validation failure
+    /// rather than unwrapping it.
+    fn expected_difficulty(&self, index: u32, parent: &Block) -> Option<u128> {
+        if index < self.height_offset + RETARGET_INTERVAL || index % RETARGET_INTERVAL != 0 {
+            return Some(parent.difficulty);
+        }
 
-        // 1. Actual index = stored index value (note that Bitcoin blocks don't store their index)
-        if block.index != i as u32 {
+        let first_block = self.ancestor_block(&parent.hash, index - RETARGET_INTERVAL)?;
+        let actual_timespan = (parent.timestamp - first_block.timestamp)
+            .clamp(TARGET_TIMESPAN / 4, TARGET_TIMESPAN * 4);
+
+        Some(parent.difficulty * actual_timespan / TARGET_TIMESPAN)
+    }
+
+    /// Proof-of-work represented by a block carrying `difficulty` as its target: the lower
+    /// the target, the more work it takes to find a hash beneath it.
+    fn block_work(difficulty: u128) -> u128 {
+        u128::MAX / difficulty.max(1)
+    }
+
+    /// Cumulative work of the main chain from `height_offset` through `height`, inclusive.
+    /// Summed with `saturating_add` rather than plain `+` -- two blocks alone at
+    /// `difficulty == 1` already overflow a `u128` sum, and `settle_branch` decides
+    /// reorgs purely by comparing these totals, so a wrapped total could make an
+    /// inferior chain look heavier (or vice versa).
+    fn chain_work_up_to(&self, height: u32) -> u128 {
+        self.blocks[..=self.relative_index(height)]
+            .iter()
+            .fold(0u128, |total, block| {
+                total.saturating_add(Self::block_work(block.difficulty))
+            })
+    }
+
+    /// Height of the main-chain block with the given hash, if any.
+    fn height_of(&self, hash: &Hash) -> Option<u32> {
+        self.blocks
+            .iter()
+            .position(|block| &block.hash == hash)
+            .map(|index| index as u32 + self.height_offset)
+    }
+
+    /// Reconstructs the UTXO set as it stood right after the main-chain block at
+    /// `height`, by undoing every main-chain block after it from a clone of
+    /// `self.unspent_outputs` -- without touching any chain state. `self.unspent_outputs`
+    /// alone reflects the current tip, not an earlier height, so a block forking off an
+    /// ancestor behind the tip needs this instead.
+    fn unspent_outputs_at(&self, height: u32) -> HashSet<Hash> {
+        let mut outputs = self.unspent_outputs.clone();
+        for block in self.blocks[self.relative_index(height) + 1..].iter().rev() {
+            for issuance in self.issuances_by_block.get(&block.hash).into_iter().flatten() {
+                for output in &issuance.outputs {
+                    outputs.remove(&output.hash());
+                }
+            }
+            if let Some((coinbase, transactions)) = block.transactions.split_first() {
+                let mut block_spent: HashSet<Hash> = HashSet::new();
+                let mut block_created: HashSet<Hash> = coinbase.output_hashes();
+                for transaction in transactions {
+                    block_spent.extend(transaction.input_hashes());
+                    block_created.extend(transaction.output_hashes());
+                }
+                outputs.retain(|output| !block_created.contains(output));
+                outputs.extend(block_spent);
+            }
+        }
+        outputs
+    }
+
+    /// Admits `block`, along with any `issuances` it introduces, onto the main chain
+    /// or a side branch. Issuances go through the same atomic pass as the block's
+    /// transactions: both must validate before either is applied, and both travel
+    /// together through a reorg (see `commit_issuances`/`unwind_issuances`), unlike a
+    /// standalone issuance call that could mutate state with no proof-of-work behind
+    /// it and no way to reconcile it by replaying the chain.
+    pub fn update_with_block(
+        &mut self,
+        block: Block,
+        issuances: Vec<IssuanceTransaction>,
+    ) -> Result<BlockLocation, BlockValidationError> {
+        if self.blocks.is_empty() {
+            Self::validate_genesis(&block)?;
+            self.validate_issuances(&issuances, None)?;
+            self.apply_transactions(&block)?;
+            self.commit_issuances(block.hash.clone(), issuances);
+            self.blocks.push(block);
+            return Ok(BlockLocation::Main { height: 0 });
+        }
+
+        let tip = self.blocks.last().unwrap();
+        if block.prev_block_hash == tip.hash {
+            let height = self.blocks.len() as u32 + self.height_offset;
+            self.validate_successor(&block, tip, height)?;
+            self.validate_issuances(&issuances, None)?;
+            self.apply_transactions(&block)?;
+            self.commit_issuances(block.hash.clone(), issuances);
+            self.blocks.push(block);
+            return Ok(BlockLocation::Main { height });
+        }
+
+        // Not extending the tip: either extend a block already known to a side branch
+        // (its current tip, or an earlier block within it -- a competing fork), or
+        // fork a brand new branch off some earlier main-chain block. Either way the
+        // block's transactions are validated against an overlay UTXO set right here,
+        // at admission time -- not deferred until (and only if) it wins a reorg. The
+        // same goes for its issuances, checked against the branch's own `asset_overlay`
+        // (falling back to `self.asset_registry` where the branch hasn't touched an
+        // asset) rather than only the main chain's committed registry, so two blocks on
+        // the same not-yet-canonical branch can't each "first" issue the same
+        // non-reissuable asset.
+        if let Some(parent) = self.side_branches.get(&block.prev_block_hash) {
+            let parent_block = parent.block.clone();
+            let fork_height = parent.fork_height;
+            let height = parent_block.index + 1;
+            self.validate_successor(&block, &parent_block, height)?;
+            self.validate_issuances(&issuances, Some(&parent.asset_overlay))?;
+
+            let available: HashSet<Hash> = self
+                .unspent_outputs_at(fork_height)
+                .difference(&parent.spent)
+                .cloned()
+                .chain(parent.created.iter().cloned())
+                .collect();
+            let (spent_here, created_here, _fee) = Self::validate_transactions(&block, &available)?;
+
+            let mut spent = parent.spent.clone();
+            spent.extend(spent_here.iter().filter(|o| !parent.created.contains(*o)).cloned());
+            let mut created: HashSet<Hash> = parent
+                .created
+                .iter()
+                .filter(|o| !spent_here.contains(*o))
+                .cloned()
+                .collect();
+            created.extend(created_here);
+            let work = parent.work.saturating_add(Self::block_work(block.difficulty));
+            let mut asset_overlay = parent.asset_overlay.clone();
+            Self::apply_issuances_to_overlay(&mut asset_overlay, &issuances);
+
+            self.side_branches.insert(
+                block.hash.clone(),
+                OffChainBlock {
+                    block: block.clone(),
+                    fork_height,
+                    spent,
+                    created,
+                    work,
+                    issuances,
+                    asset_overlay,
+                },
+            );
+            return self.settle_branch(block.hash.clone());
+        }
+
+        if let Some(fork_height) = self.height_of(&block.prev_block_hash) {
+            let parent = self.blocks[self.relative_index(fork_height)].clone();
+            let height = fork_height + 1;
+            self.validate_successor(&block, &parent, height)?;
+            self.validate_issuances(&issuances, None)?;
+
+            // `fork_height` may sit behind the current tip, so the outputs available
+            // there aren't simply `self.unspent_outputs` -- that reflects the tip, not
+            // this historical point.
+            let available = self.unspent_outputs_at(fork_height);
+            let (spent, created, _fee) = Self::validate_transactions(&block, &available)?;
+            let work = Self::block_work(block.difficulty);
+            let mut asset_overlay = HashMap::new();
+            Self::apply_issuances_to_overlay(&mut asset_overlay, &issuances);
+
+            self.side_branches.insert(
+                block.hash.clone(),
+                OffChainBlock {
+                    block: block.clone(),
+                    fork_height,
+                    spent,
+                    created,
+                    work,
+                    issuances,
+                    asset_overlay,
+                },
+            );
+            return self.settle_branch(block.hash.clone());
+        }
+
+        Err(BlockValidationError::UnknownParent)
+    }
+
+    /// Reorganizes the main chain onto the side branch ending at `tip_hash` if it now
+    /// carries more cumulative work than the current tip.
+    fn settle_branch(&mut self, tip_hash: Hash) -> Result<BlockLocation, BlockValidationError> {
+        let off_chain = &self.side_branches[&tip_hash];
+        let fork_height = off_chain.fork_height;
+        let height = off_chain.block.index;
+        let total_branch_work = self.chain_work_up_to(fork_height).saturating_add(off_chain.work);
+        let tip_height = self.blocks.len() as u32 - 1 + self.height_offset;
+
+        if total_branch_work > self.chain_work_up_to(tip_height) {
+            self.reorganize(tip_hash)?;
+            Ok(BlockLocation::Main { height })
+        } else {
+            Ok(BlockLocation::Side { height })
+        }
+    }
+
+    /// Rewinds the main chain back to the fork point of the side branch ending at
+    /// `tip_hash` by un-applying the displaced blocks' effect on `unspent_outputs`,
+    /// then replays the branch on top, making it canonical. The displaced suffix is
+    /// kept around as a new side branch in case it regains the lead.
+    ///
+    /// Every block in the branch was already validated against an overlay UTXO set
+    /// as it was admitted by `update_with_block`, so `apply_transactions` is not
+    /// expected to fail here -- but its `Result` is still propagated, rather than
+    /// asserted with `.expect(...)`, so a mistake in that invariant can't panic the
+    /// node on attacker-supplied blocks.
+    fn reorganize(&mut self, tip_hash: Hash) -> Result<(), BlockValidationError> {
+        let mut chain = vec![];
+        let mut hash = tip_hash;
+        while let Some(off_chain) = self.side_branches.remove(&hash) {
+            hash = off_chain.block.prev_block_hash.clone();
+            chain.push((off_chain.block, off_chain.issuances));
+        }
+        chain.reverse();
+
+        let fork_height = chain[0].0.index - 1;
+        let displaced = self.blocks.split_off(self.relative_index(fork_height) + 1);
+        let mut displaced_issuances = vec![];
+        for block in displaced.iter().rev() {
+            self.unwind_transactions(block);
+            displaced_issuances.push(self.unwind_issuances(&block.hash));
+        }
+        displaced_issuances.reverse();
+
+        // Any *other* side branch that forked off a block within `displaced` shared
+        // that ancestor with the main chain a moment ago, but that block is now itself
+        // only reachable as part of the side branch `reattach_as_side_branch` is about
+        // to create below -- not `self.blocks` -- so its recorded `fork_height` would
+        // index past the (possibly now-shorter) main chain the next time it's extended.
+        self.rebase_orphaned_branches(fork_height, &displaced);
+
+        for (block, issuances) in &chain {
+            self.apply_transactions(block)?;
+            self.commit_issuances(block.hash.clone(), issuances.clone());
+        }
+        self.blocks.extend(chain.into_iter().map(|(block, _)| block));
+
+        if !displaced.is_empty() {
+            self.reattach_as_side_branch(fork_height, displaced, displaced_issuances);
+        }
+
+        Ok(())
+    }
+
+    /// Moves every remaining side branch whose `fork_height` falls within `displaced`
+    /// down to `new_fork_height`, folding the now-displaced work between the two
+    /// heights into the branch's own `work` tally so its total is still comparable to
+    /// `chain_work_up_to(new_fork_height)`. Without this, `settle_branch` would later
+    /// call `chain_work_up_to` with a `fork_height` no longer present on `self.blocks`
+    /// and index out of bounds -- reachable simply by having a second, older side
+    /// branch in flight when a reorg shrinks the chain.
+    fn rebase_orphaned_branches(&mut self, new_fork_height: u32, displaced: &[Block]) {
+        let mut cumulative = 0u128;
+        let mut extra_work_at: HashMap<u32, u128> = HashMap::new();
+        for block in displaced {
+            cumulative = cumulative.saturating_add(Self::block_work(block.difficulty));
+            extra_work_at.insert(block.index, cumulative);
+        }
+
+        for off_chain in self.side_branches.values_mut() {
+            if let Some(extra_work) = extra_work_at.get(&off_chain.fork_height) {
+                off_chain.work = off_chain.work.saturating_add(*extra_work);
+                off_chain.fork_height = new_fork_height;
+            }
+        }
+    }
+
+    /// Re-registers `displaced` (blocks just kicked off the main chain by a reorg,
+    /// paired with the issuances each one had introduced) as a new side branch forked
+    /// at `fork_height`, rebuilding each block's overlay bookkeeping the same way a
+    /// freshly admitted fork would. These blocks were canonical a moment ago, so this
+    /// only rebuilds bookkeeping -- it doesn't re-validate transactions or issuances
+    /// that were already proven good.
+    fn reattach_as_side_branch(
+        &mut self,
+        fork_height: u32,
+        displaced: Vec<Block>,
+        displaced_issuances: Vec<Vec<IssuanceTransaction>>,
+    ) {
+        let mut spent: HashSet<Hash> = HashSet::new();
+        let mut created: HashSet<Hash> = HashSet::new();
+        let mut work = 0u128;
+        let mut asset_overlay: HashMap<Hash, AssetRecord> = HashMap::new();
+
+        for (block, issuances) in displaced.into_iter().zip(displaced_issuances) {
+            if let Some((coinbase, transactions)) = block.transactions.split_first() {
+                let mut block_spent = HashSet::new();
+                let mut block_created: HashSet<Hash> = coinbase.output_hashes();
+                for transaction in transactions {
+                    block_spent.extend(transaction.input_hashes());
+                    block_created.extend(transaction.output_hashes());
+                }
+                spent.extend(block_spent.iter().filter(|o| !created.contains(*o)).cloned());
+                created.retain(|output| !block_spent.contains(output));
+                created.extend(block_created);
+            }
+
+            work = work.saturating_add(Self::block_work(block.difficulty));
+            Self::apply_issuances_to_overlay(&mut asset_overlay, &issuances);
+            self.side_branches.insert(
+                block.hash.clone(),
+                OffChainBlock {
+                    block,
+                    fork_height,
+                    spent: spent.clone(),
+                    created: created.clone(),
+                    work,
+                    issuances,
+                    asset_overlay: asset_overlay.clone(),
+                },
+            );
+        }
+    }
+
+    fn validate_genesis(block: &Block) -> Result<(), BlockValidationError> {
+        if block.index != 0 {
+            Err(BlockValidationError::MismatchedIndex)
+        } else if block.prev_block_hash != vec![0; 32] {
+            Err(BlockValidationError::InvalidGenesisBlockFormat)
+        } else if !check_difficulty(&block.hash(), block.difficulty) {
+            Err(BlockValidationError::InvalidHash)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Validates `block` as the successor of `parent` at `expected_index`, regardless of
+    /// whether that successor lands on the main chain or a side branch. Difficulty
+    /// retargeting (`expected_difficulty`) is computed from `parent`'s own ancestry via
+    /// `ancestor_block`, so a side branch is held to the same retargeting rule the main
+    /// chain is -- rather than its parent's fixed difficulty forever -- without having
+    /// to first know whether `parent` is the real main-chain tip.
+    fn validate_successor(
+        &self,
+        block: &Block,
+        parent: &Block,
+        expected_index: u32,
+    ) -> Result<(), BlockValidationError> {
+        if block.index != expected_index {
             return Err(BlockValidationError::MismatchedIndex);
-            // 2. Block's hash fits stored difficulty value (we will trust the difficulty value for now ⚠️INSECURE)
+        }
+
+        let expected_difficulty = self
+            .expected_difficulty(expected_index, parent)
+            .ok_or(BlockValidationError::UnexpectedDifficulty)?;
+        if block.difficulty != expected_difficulty {
+            return Err(BlockValidationError::UnexpectedDifficulty);
         } else if !check_difficulty(&block.hash(), block.difficulty) {
             return Err(BlockValidationError::InvalidHash);
-        } else if i != 0 {
-            // Not Genesis block:
-            // 3. Time is always increasing
-            if block.timestamp <= prev_block.timestamp {
-                return Err(BlockValidationError::AchronologicalTimestamps);
-            // 4. Actual prev_block_hash = stored prev_block_hash value (except for the genesis block)
-            } else if block.prev_block_hash != prev_block.hash {
-                return Err(BlockValidationError::MismatchedPreviousHash);
+        }
+
+        if block.timestamp <= parent.timestamp {
+            return Err(BlockValidationError::AchronologicalTimestamps);
+        } else if block.prev_block_hash != parent.hash {
+            return Err(BlockValidationError::MismatchedPreviousHash);
+        }
+
+        Ok(())
+    }
+
+    /// Validates `block`'s transactions against `unspent_outputs` and applies their effect.
+    fn apply_transactions(&mut self, block: &Block) -> Result<(), BlockValidationError> {
+        let (block_spent, block_created, _total_fee) =
+            Self::validate_transactions(block, &self.unspent_outputs)?;
+
+        self.unspent_outputs
+            .retain(|output| !block_spent.contains(output));
+        self.unspent_outputs.extend(block_created);
+
+        Ok(())
+    }
+
+    /// Checks `block`'s transactions against `available` (the outputs considered
+    /// spendable for this check) without touching any chain state, returning the
+    /// subset of `available` it consumes, the outputs it creates, and its total
+    /// native-asset fee. Shared by `apply_transactions` (checked against
+    /// `self.unspent_outputs`) and side-branch admission in `update_with_block`
+    /// (checked against an overlay of the branch's own pending spends/creates), so a
+    /// side branch block is held to exactly the same rules a main-chain block is,
+    /// instead of only being checked structurally until (and unless) it wins a reorg.
+    fn validate_transactions(
+        block: &Block,
+        available: &HashSet<Hash>,
+    ) -> Result<(HashSet<Hash>, HashSet<Hash>, u64), BlockValidationError> {
+        let (coinbase, transactions) = match block.transactions.split_first() {
+            Some(parts) => parts,
+            None => return Ok((HashSet::new(), HashSet::new(), 0)),
+        };
+        if !coinbase.is_coinbase() {
+            return Err(BlockValidationError::InvalidCoinbaseTransaction);
+        }
+
+        let mut block_spent: HashSet<Hash> = HashSet::new();
+        let mut block_created: HashSet<Hash> = HashSet::new();
+        let mut total_fee = 0;
+
+        for transaction in transactions {
+            let input_hashes = transaction.input_hashes();
+            let output_hashes = transaction.output_hashes();
+
+            if !(&input_hashes - available).is_empty() || !(&input_hashes & &block_spent).is_empty()
+            {
+                return Err(BlockValidationError::InvalidInput);
             }
-        } else {
-            // Genesis block:
-            if block.prev_block_hash != vec![0; 32] {
-                return Err(BlockValidationError::InvalidGenesisBlockFormat);
+
+            // The signature hash binds each input's unlocking proof to this exact
+            // transaction, so it can't be replayed against a competing spend of the
+            // same output.
+            let sighash = transaction.hash();
+            if transaction
+                .inputs
+                .iter()
+                .any(|input| !input.verify_script(&sighash))
+            {
+                return Err(BlockValidationError::ScriptVerificationFailed);
             }
-        }
 
-        if let Some((coinbase, transactions)) = block.transactions.split_first() {
-            if !coinbase.is_coinbase() {
-                return Err(BlockValidationError::InvalidCoinbaseTransaction);
+            let mut input_by_asset: HashMap<Hash, u64> = HashMap::new();
+            for input in &transaction.inputs {
+                *input_by_asset.entry(input.asset_id.clone()).or_insert(0) += input.value;
+            }
+            let mut output_by_asset: HashMap<Hash, u64> = HashMap::new();
+            for output in &transaction.outputs {
+                *output_by_asset.entry(output.asset_id.clone()).or_insert(0) += output.value;
             }
-            let mut block_spent: HashSet<Hash> = HashSet::new();
-            let mut block_created: HashSet<Hash> = HashSet::new();
-            let mut total_fee = 0;
 
-            for transaction in transactions {
-                let input_hashes = transaction.input_hashes();
-                let output_hashes = transaction.output_hashes();
-
-                if !(&input_hashes - &self.unspent_outputs).is_empty()
-                    || !(&input_hashes & &block_spent).is_empty()
-                // check for uncommon ones
-                {
-                    return Err(BlockValidationError::InvalidInput);
+            // "output <= input" is enforced per asset; only the native asset's
+            // surplus becomes the transaction's fee, a surplus in any other asset
+            // is allowed but not collected.
+            for (asset_id, output_total) in &output_by_asset {
+                let input_total = input_by_asset.get(asset_id).copied().unwrap_or(0);
+                if *output_total > input_total {
+                    return Err(BlockValidationError::AssetImbalance);
                 }
+            }
 
-                let input_value = transaction.input_value();
-                let output_value = transaction.output_value();
-                if output_value > input_value {
-                    return Err(BlockValidationError::InsufficientInputValue);
-                }
+            let native_asset = native_asset();
+            let native_input = input_by_asset.get(&native_asset).copied().unwrap_or(0);
+            let native_output = output_by_asset.get(&native_asset).copied().unwrap_or(0);
+            total_fee += native_input - native_output;
 
-                let fee = input_value - output_value;
-                total_fee += fee;
+            block_spent.extend(input_hashes);
+            block_created.extend(output_hashes);
+        }
 
-                block_spent.extend(input_hashes);
-                block_created.extend(output_hashes);
-            }
+        if coinbase.output_value() < total_fee {
+            return Err(BlockValidationError::InvalidCoinbaseTransaction);
+        }
+        block_created.extend(coinbase.output_hashes());
 
-            if coinbase.output_value() < total_fee {
-                return Err(BlockValidationError::InvalidCoinbaseTransaction);
-            } else {
-                block_created.extend(coinbase.output_hashes());
+        Ok((block_spent, block_created, total_fee))
+    }
+
+    /// Reverses `apply_transactions`: drops the outputs `block` created from the UTXO
+    /// set and restores the outputs it spent, so it can be disconnected during a reorg.
+    fn unwind_transactions(&mut self, block: &Block) {
+        if let Some((coinbase, transactions)) = block.transactions.split_first() {
+            let mut block_spent: HashSet<Hash> = HashSet::new();
+            let mut block_created: HashSet<Hash> = coinbase.output_hashes();
+
+            for transaction in transactions {
+                block_spent.extend(transaction.input_hashes());
+                block_created.extend(transaction.output_hashes());
             }
 
             self.unspent_outputs
-                .retain(|output| !block_spent.contains(output));
+                .retain(|output| !block_created.contains(output));
+            self.unspent_outputs.extend(block_spent);
+        }
+    }
 
-            self.unspent_outputs.extend(block_created);
+    /// Serializes the full UTXO set and asset registry as of `height`, for an importer
+    /// to check against a trusted header via `import_utxo_snapshot`. The asset registry
+    /// has to travel with the UTXO set, not just alongside it -- a node that resumed
+    /// from a snapshot missing it would have no memory of which assets were already
+    /// issued, and would accept a "reissuance" of a fixed-supply asset as if it were
+    /// the first.
+    ///
+    /// Deliberately doesn't carry its own committed root: a root field sitting right
+    /// here on the struct the importer just received is too easy to pass back in as
+    /// `import_utxo_snapshot`'s `trusted_root`, which would silently recreate the "root
+    /// recomputed from its own claims" hole that field was introduced to close in the
+    /// first place. `committed_root` is still computed from the snapshot's contents at
+    /// import time -- the importer just has to source what it's checked *against* from
+    /// somewhere else.
+    pub fn export_utxo_snapshot(&self, height: u32) -> UtxoSnapshot {
+        let mut outputs: Vec<Hash> = self.unspent_outputs_at(height).into_iter().collect();
+        outputs.sort();
+        let mut registry = self.asset_registry.clone();
+        for block in self.blocks[self.relative_index(height) + 1..].iter().rev() {
+            for issuance in self.issuances_by_block.get(&block.hash).into_iter().flatten().rev() {
+                if let Some(record) = registry.get_mut(&issuance.asset_id) {
+                    record.issuance_count -= 1;
+                    if record.issuance_count == 0 {
+                        registry.remove(&issuance.asset_id);
+                    }
+                }
+            }
         }
+        let mut asset_registry: Vec<(Hash, bool, u32)> = registry
+            .iter()
+            .map(|(asset_id, record)| (asset_id.clone(), record.reissuable, record.issuance_count))
+            .collect();
+        asset_registry.sort_by(|a, b| a.0.cmp(&b.0));
 
-        self.blocks.push(block);
+        UtxoSnapshot {
+            height,
+            block_hash: self.blocks[self.relative_index(height)].hash.clone(),
+            outputs,
+            asset_registry,
+        }
+    }
+
+    /// Installs `snapshot` as the starting state for a fast-synced node and resumes
+    /// normal `update_with_block` validation from `header` onward, without replaying
+    /// any block before it. `trusted_root` must come from somewhere the importer
+    /// actually trusts independently of `snapshot` itself -- a checkpoint baked into
+    /// the client, or a root carried by `header` once this crate's header format
+    /// commits to one -- never recomputed from `snapshot.outputs`/`snapshot.asset_registry`,
+    /// or a peer serving a forged UTXO set or asset registry could simply recompute a
+    /// matching root over its own lie and sail through the check.
+    pub fn import_utxo_snapshot(
+        &mut self,
+        snapshot: UtxoSnapshot,
+        header: Block,
+        trusted_root: &Hash,
+    ) -> Result<(), BlockValidationError> {
+        if header.index != snapshot.height
+            || header.hash != snapshot.block_hash
+            || committed_root(&snapshot.outputs, &snapshot.asset_registry) != *trusted_root
+        {
+            return Err(BlockValidationError::InvalidSnapshot);
+        }
+
+        self.height_offset = snapshot.height;
+        self.unspent_outputs = snapshot.outputs.into_iter().collect();
+        self.asset_registry = snapshot
+            .asset_registry
+            .into_iter()
+            .map(|(asset_id, reissuable, issuance_count)| {
+                (
+                    asset_id,
+                    AssetRecord {
+                        reissuable,
+                        issuance_count,
+                    },
+                )
+            })
+            .collect();
+        self.issuances_by_block.clear();
+        self.side_branches.clear();
+        self.blocks = vec![header];
         Ok(())
     }
 }
+
+/// A serializable checkpoint of the UTXO set and asset registry at a given height,
+/// letting a new node skip straight to validating from there instead of replaying
+/// every block from genesis.
+pub struct UtxoSnapshot {
+    pub height: u32,
+    pub block_hash: Hash,
+    pub outputs: Vec<Hash>,
+    /// `(asset_id, reissuable, issuance_count)` for every asset issued up to `height`,
+    /// mirroring `AssetRecord` -- see `Blockchain::asset_registry`.
+    pub asset_registry: Vec<(Hash, bool, u32)>,
+}
+
+struct SnapshotBytes(Vec<u8>);
+
+impl Hashable for SnapshotBytes {
+    fn bytes(&self) -> Vec<u8> {
+        self.0.clone()
+    }
+}
+
+/// Commits to a (sorted) set of output hashes and a (sorted) asset registry so a
+/// snapshot's contents can be checked against an independently trusted root (see
+/// `Blockchain::import_utxo_snapshot`).
+fn committed_root(sorted_outputs: &[Hash], sorted_asset_registry: &[(Hash, bool, u32)]) -> Hash {
+    let mut bytes = vec![];
+    for output in sorted_outputs {
+        bytes.extend(output);
+    }
+    for (asset_id, reissuable, issuance_count) in sorted_asset_registry {
+        bytes.extend(asset_id);
+        bytes.push(*reissuable as u8);
+        bytes.extend(issuance_count.to_be_bytes());
+    }
+    SnapshotBytes(bytes).hash()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr() -> Address {
+        "test-address".to_owned()
+    }
+
+    /// A block holding only a coinbase paying `value`, mined at a trivial difficulty so
+    /// `check_difficulty` always accepts it -- enough to exercise fork-choice/reorg
+    /// without needing real signatures anywhere in the chain.
+    fn coinbase_block(index: u32, prev_block_hash: Hash, timestamp: u128, value: u64) -> Block {
+        let coinbase = Transaction {
+            inputs: vec![],
+            outputs: vec![Output {
+                to_addr: addr(),
+                value,
+                asset_id: native_asset(),
+                locking_script: vec![0; 32],
+                unlocking_pubkey: vec![],
+                unlocking_signature: vec![],
+            }],
+        };
+        Block::new(index, timestamp, prev_block_hash, vec![coinbase], u128::MAX)
+    }
+
+    #[test]
+    fn reorg_adopts_the_side_branch_once_it_outweighs_the_main_chain() {
+        let mut chain = Blockchain::new();
+
+        let genesis = coinbase_block(0, vec![0; 32], 1, 50);
+        let genesis_hash = genesis.hash.clone();
+        chain.update_with_block(genesis, vec![]).unwrap();
+
+        let main_1 = coinbase_block(1, genesis_hash.clone(), 2, 50);
+        let main_1_hash = main_1.hash.clone();
+        assert_eq!(
+            chain.update_with_block(main_1, vec![]).unwrap(),
+            BlockLocation::Main { height: 1 }
+        );
+
+        // A competing block at the same height is held on a side branch...
+        let side_1 = coinbase_block(1, genesis_hash, 3, 50);
+        let side_1_hash = side_1.hash.clone();
+        assert_eq!(
+            chain.update_with_block(side_1, vec![]).unwrap(),
+            BlockLocation::Side { height: 1 }
+        );
+
+        // ...until it pulls ahead on cumulative work, at which point it becomes
+        // canonical and the old tip is displaced onto a side branch of its own.
+        let side_2 = coinbase_block(2, side_1_hash, 4, 50);
+        assert_eq!(
+            chain.update_with_block(side_2, vec![]).unwrap(),
+            BlockLocation::Main { height: 2 }
+        );
+
+        assert_eq!(chain.blocks.len(), 3);
+        assert!(chain.side_branches.contains_key(&main_1_hash));
+    }
+
+    #[test]
+    fn block_with_a_forged_unlocking_signature_is_rejected() {
+        let mut chain = Blockchain::new();
+
+        let genesis = coinbase_block(0, vec![0; 32], 1, 50);
+        let genesis_hash = genesis.hash.clone();
+        let spendable = genesis.transactions[0].outputs[0].hash();
+        chain.update_with_block(genesis, vec![]).unwrap();
+        assert!(chain.unspent_outputs.contains(&spendable));
+
+        // Claims to spend the genesis coinbase output but its unlocking pubkey doesn't
+        // hash to that output's `locking_script`, so the spend must fail regardless of
+        // whether an accompanying signature is well-formed.
+        let spend = Transaction {
+            inputs: vec![Output {
+                to_addr: addr(),
+                value: 50,
+                asset_id: native_asset(),
+                locking_script: vec![0; 32],
+                unlocking_pubkey: vec![9; 32],
+                unlocking_signature: vec![9; 64],
+            }],
+            outputs: vec![Output {
+                to_addr: addr(),
+                value: 50,
+                asset_id: native_asset(),
+                locking_script: vec![1; 32],
+                unlocking_pubkey: vec![],
+                unlocking_signature: vec![],
+            }],
+        };
+        assert_eq!(spend.inputs[0].hash(), spendable);
+
+        let coinbase = coinbase_block(1, genesis_hash.clone(), 2, 0).transactions.remove(0);
+        let block = Block::new(1, 2, genesis_hash, vec![coinbase, spend], u128::MAX);
+
+        assert!(matches!(
+            chain.update_with_block(block, vec![]),
+            Err(BlockValidationError::ScriptVerificationFailed)
+        ));
+    }
+
+    #[test]
+    fn ancestor_block_walks_back_through_a_side_branch_to_the_main_chain() {
+        let mut chain = Blockchain::new();
+
+        let genesis = coinbase_block(0, vec![0; 32], 1, 50);
+        let genesis_hash = genesis.hash.clone();
+        chain.update_with_block(genesis.clone(), vec![]).unwrap();
+
+        let main_1 = coinbase_block(1, genesis_hash.clone(), 2, 50);
+        let main_1_hash = main_1.hash.clone();
+        chain.update_with_block(main_1, vec![]).unwrap();
+
+        // A third main-chain block keeps the main chain at least as heavy as the
+        // two-block side branch below, so that branch is not adopted by a reorg.
+        let main_2 = coinbase_block(2, main_1_hash, 3, 50);
+        chain.update_with_block(main_2, vec![]).unwrap();
+
+        // A side branch forked off the genesis block, two blocks deep.
+        let side_1 = coinbase_block(1, genesis_hash, 3, 50);
+        let side_1_hash = side_1.hash.clone();
+        chain.update_with_block(side_1.clone(), vec![]).unwrap();
+
+        let side_2 = coinbase_block(2, side_1_hash, 4, 50);
+        let side_2_hash = side_2.hash.clone();
+        chain.update_with_block(side_2.clone(), vec![]).unwrap();
+        assert!(chain.side_branches.contains_key(&side_2_hash));
+
+        // Within the branch's own ancestry...
+        assert_eq!(chain.ancestor_block(&side_2_hash, 2).unwrap().hash, side_2.hash);
+        assert_eq!(chain.ancestor_block(&side_2_hash, 1).unwrap().hash, side_1.hash);
+        // ...and past the fork point, onto the main chain it actually forked from.
+        assert_eq!(chain.ancestor_block(&side_2_hash, 0).unwrap().hash, genesis.hash);
+    }
+
+    #[test]
+    fn expected_difficulty_is_unchanged_before_the_first_retarget_boundary() {
+        let mut chain = Blockchain::new();
+
+        let genesis = coinbase_block(0, vec![0; 32], 1, 50);
+        chain.update_with_block(genesis.clone(), vec![]).unwrap();
+
+        // RETARGET_INTERVAL blocks haven't been mined yet, so the next block is still
+        // held to its parent's difficulty, not a freshly computed one.
+        assert_eq!(
+            chain.expected_difficulty(1, &genesis).unwrap(),
+            genesis.difficulty
+        );
+    }
+
+    #[test]
+    fn expected_difficulty_retargets_and_clamps_at_the_interval_boundary() {
+        // A modest difficulty, carried forward unchanged by every block below the
+        // retarget boundary -- large enough to see the retarget take effect, small
+        // enough that multiplying it by a clamped timespan ratio can't itself overflow.
+        let starting_difficulty = 1_000_000u128;
+
+        // Mine a full retarget window's worth of blocks ten seconds apart -- far faster
+        // than the one-per-ten-minutes target -- so the boundary block's retarget is
+        // clamped to the steepest allowed adjustment rather than scaled by the raw
+        // (and much more extreme) ratio.
+        let mut chain = Blockchain::new();
+        let mut prev_hash = vec![0; 32];
+        for index in 0..RETARGET_INTERVAL {
+            let block = Block::new(
+                index,
+                (index as u128 + 1) * 10,
+                prev_hash,
+                vec![coinbase_block(index, vec![0; 32], 1, 50).transactions.remove(0)],
+                starting_difficulty,
+            );
+            prev_hash = block.hash.clone();
+            chain.update_with_block(block, vec![]).unwrap();
+        }
+
+        let parent = chain.blocks.last().unwrap().clone();
+        let expected = chain.expected_difficulty(RETARGET_INTERVAL, &parent).unwrap();
+
+        assert_eq!(
+            expected,
+            parent.difficulty * (TARGET_TIMESPAN / 4) / TARGET_TIMESPAN
+        );
+    }
+
+    #[test]
+    fn a_snapshot_round_trips_into_a_resumed_chain() {
+        let mut chain = Blockchain::new();
+        let genesis = coinbase_block(0, vec![0; 32], 1, 50);
+        let genesis_hash = genesis.hash.clone();
+        chain.update_with_block(genesis, vec![]).unwrap();
+
+        let main_1 = coinbase_block(1, genesis_hash, 2, 50);
+        chain.update_with_block(main_1.clone(), vec![]).unwrap();
+
+        let snapshot = chain.export_utxo_snapshot(1);
+        assert_eq!(snapshot.block_hash, main_1.hash);
+        let trusted_root = committed_root(&snapshot.outputs, &snapshot.asset_registry);
+
+        let mut resumed = Blockchain::new();
+        resumed
+            .import_utxo_snapshot(snapshot, main_1.clone(), &trusted_root)
+            .unwrap();
+
+        assert_eq!(resumed.blocks.len(), 1);
+        assert_eq!(resumed.blocks[0].hash, main_1.hash);
+        assert_eq!(resumed.unspent_outputs, chain.unspent_outputs);
+
+        // The resumed chain can keep extending right from the imported tip.
+        let main_2 = coinbase_block(2, resumed.blocks[0].hash.clone(), 3, 50);
+        assert_eq!(
+            resumed.update_with_block(main_2, vec![]).unwrap(),
+            BlockLocation::Main { height: 2 }
+        );
+    }
+
+    #[test]
+    fn import_rejects_a_snapshot_that_doesnt_match_the_trusted_root() {
+        let mut chain = Blockchain::new();
+        let genesis = coinbase_block(0, vec![0; 32], 1, 50);
+        chain.update_with_block(genesis.clone(), vec![]).unwrap();
+
+        let snapshot = chain.export_utxo_snapshot(0);
+        let forged_root = vec![0xff; 32];
+
+        let mut resumed = Blockchain::new();
+        assert!(matches!(
+            resumed.import_utxo_snapshot(snapshot, genesis, &forged_root),
+            Err(BlockValidationError::InvalidSnapshot)
+        ));
+    }
+
+    fn issuance(asset_id: Hash, value: u64, reissuable: bool) -> IssuanceTransaction {
+        IssuanceTransaction {
+            asset_id: asset_id.clone(),
+            outputs: vec![Output {
+                to_addr: addr(),
+                value,
+                asset_id,
+                locking_script: vec![0; 32],
+                unlocking_pubkey: vec![],
+                unlocking_signature: vec![],
+            }],
+            reissuable,
+        }
+    }
+
+    #[test]
+    fn update_with_block_rejects_reissuing_a_non_reissuable_asset() {
+        let mut chain = Blockchain::new();
+        let asset_id = vec![5; 32];
+
+        let genesis = coinbase_block(0, vec![0; 32], 1, 50);
+        let genesis_hash = genesis.hash.clone();
+        chain
+            .update_with_block(genesis, vec![issuance(asset_id.clone(), 100, false)])
+            .unwrap();
+
+        let main_1 = coinbase_block(1, genesis_hash, 2, 50);
+        assert!(matches!(
+            chain.update_with_block(main_1, vec![issuance(asset_id, 50, false)]),
+            Err(BlockValidationError::AssetAlreadyIssued)
+        ));
+    }
+
+    #[test]
+    fn update_with_block_allows_reissuing_a_reissuable_asset() {
+        let mut chain = Blockchain::new();
+        let asset_id = vec![6; 32];
+
+        let genesis = coinbase_block(0, vec![0; 32], 1, 50);
+        let genesis_hash = genesis.hash.clone();
+        chain
+            .update_with_block(genesis, vec![issuance(asset_id.clone(), 100, true)])
+            .unwrap();
+
+        let main_1 = coinbase_block(1, genesis_hash, 2, 50);
+        assert_eq!(
+            chain
+                .update_with_block(main_1, vec![issuance(asset_id, 50, true)])
+                .unwrap(),
+            BlockLocation::Main { height: 1 }
+        );
+    }
+
+    #[test]
+    fn update_with_block_rejects_a_transaction_that_mints_more_of_an_asset_than_it_spends() {
+        let mut chain = Blockchain::new();
+
+        let genesis = coinbase_block(0, vec![0; 32], 1, 50);
+        let genesis_hash = genesis.hash.clone();
+        chain.update_with_block(genesis, vec![]).unwrap();
+
+        // No inputs at all, yet an output claiming a non-native asset's value -- can
+        // never balance, the same per-asset rule `Mempool::add`'s `is_balanced` enforces
+        // ahead of time for a transaction still sitting in the pool.
+        let overmint = Transaction {
+            inputs: vec![],
+            outputs: vec![Output {
+                to_addr: addr(),
+                value: 50,
+                asset_id: vec![7; 32],
+                locking_script: vec![0; 32],
+                unlocking_pubkey: vec![],
+                unlocking_signature: vec![],
+            }],
+        };
+        let coinbase = coinbase_block(1, genesis_hash.clone(), 2, 0).transactions.remove(0);
+        let block = Block::new(1, 2, genesis_hash, vec![coinbase, overmint], u128::MAX);
+
+        assert!(matches!(
+            chain.update_with_block(block, vec![]),
+            Err(BlockValidationError::AssetImbalance)
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::{HashMap, HashSet};
+
+use super::*;
+
+#[derive(Debug)]
+pub enum MempoolError {
+    /// One or more inputs don't reference a currently unspent output.
+    UnknownInput,
+    /// One or more inputs are already spent by another transaction sitting in the mempool.
+    DoubleSpend,
+    /// One or more inputs' unlocking data doesn't satisfy its referenced output's
+    /// locking script over this transaction's signature hash.
+    ScriptVerificationFailed,
+    /// Some asset's outputs exceed its inputs.
+    AssetImbalance,
+}
+
+/// A pool of unconfirmed transactions, validated against the chain's current UTXO set
+/// and ready to be picked up by a `BlockAssembler`.
+pub struct Mempool {
+    transactions: Vec<Transaction>,
+    spent_outputs: HashSet<Hash>,
+}
+
+impl Mempool {
+    pub fn new() -> Self {
+        Mempool {
+            transactions: vec![],
+            spent_outputs: HashSet::new(),
+        }
+    }
+
+    /// Admits `transaction` if its inputs are unspent and not already claimed by another
+    /// mempool transaction, reusing the same checks `Blockchain::update_with_block`
+    /// applies -- including script verification, so a transaction that would fail
+    /// `ScriptVerificationFailed` the moment it's mined never sits in the mempool or
+    /// gets greedily selected into a `BlockAssembler` template in the first place.
+    pub fn add(
+        &mut self,
+        transaction: Transaction,
+        unspent_outputs: &HashSet<Hash>,
+    ) -> Result<(), MempoolError> {
+        let input_hashes = transaction.input_hashes();
+
+        if !(&input_hashes - unspent_outputs).is_empty() {
+            return Err(MempoolError::UnknownInput);
+        }
+        if !(&input_hashes & &self.spent_outputs).is_empty() {
+            return Err(MempoolError::DoubleSpend);
+        }
+
+        let sighash = transaction.hash();
+        if transaction
+            .inputs
+            .iter()
+            .any(|input| !input.verify_script(&sighash))
+        {
+            return Err(MempoolError::ScriptVerificationFailed);
+        }
+
+        if !is_balanced(&transaction) {
+            return Err(MempoolError::AssetImbalance);
+        }
+
+        self.spent_outputs.extend(input_hashes);
+        self.transactions.push(transaction);
+        Ok(())
+    }
+
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+}
+
+/// Checks that, for every asset, `transaction`'s outputs don't exceed its inputs --
+/// the same per-asset rule `Blockchain::apply_transactions` enforces at block level,
+/// applied early so an over-spending transaction can never sit in the mempool.
+fn is_balanced(transaction: &Transaction) -> bool {
+    let mut input_by_asset: HashMap<Hash, u64> = HashMap::new();
+    for input in &transaction.inputs {
+        *input_by_asset.entry(input.asset_id.clone()).or_insert(0) += input.value;
+    }
+
+    let mut output_by_asset: HashMap<Hash, u64> = HashMap::new();
+    for output in &transaction.outputs {
+        *output_by_asset.entry(output.asset_id.clone()).or_insert(0) += output.value;
+    }
+
+    output_by_asset
+        .iter()
+        .all(|(asset_id, total)| *total <= input_by_asset.get(asset_id).copied().unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr() -> Address {
+        "test-address".to_owned()
+    }
+
+    fn output(value: u64, locking_script: Hash) -> Output {
+        Output {
+            to_addr: addr(),
+            value,
+            asset_id: native_asset(),
+            locking_script,
+            unlocking_pubkey: vec![],
+            unlocking_signature: vec![],
+        }
+    }
+
+    #[test]
+    fn add_rejects_an_input_that_isnt_currently_unspent() {
+        let mut pool = Mempool::new();
+        let spend = Transaction {
+            inputs: vec![output(50, vec![0; 32])],
+            outputs: vec![],
+        };
+
+        assert!(matches!(
+            pool.add(spend, &HashSet::new()),
+            Err(MempoolError::UnknownInput)
+        ));
+    }
+
+    #[test]
+    fn add_rejects_a_double_spend_against_another_mempool_transaction() {
+        let mut pool = Mempool::new();
+        let input = output(50, vec![0; 32]);
+        let unspent: HashSet<Hash> = [input.hash()].into_iter().collect();
+
+        // Already claimed by some other transaction sitting in the pool.
+        pool.spent_outputs.insert(input.hash());
+
+        let spend = Transaction {
+            inputs: vec![input],
+            outputs: vec![],
+        };
+        assert!(matches!(
+            pool.add(spend, &unspent),
+            Err(MempoolError::DoubleSpend)
+        ));
+    }
+
+    #[test]
+    fn add_rejects_a_script_that_doesnt_unlock_its_input() {
+        let mut pool = Mempool::new();
+        // The input's locking script doesn't match any pubkey this spend supplies, so
+        // it must fail regardless of what signature accompanies it.
+        let input = output(50, vec![0; 32]);
+        let unspent: HashSet<Hash> = [input.hash()].into_iter().collect();
+
+        let spend = Transaction {
+            inputs: vec![input],
+            outputs: vec![],
+        };
+        assert!(matches!(
+            pool.add(spend, &unspent),
+            Err(MempoolError::ScriptVerificationFailed)
+        ));
+    }
+
+    #[test]
+    fn add_rejects_an_asset_imbalance() {
+        let mut pool = Mempool::new();
+        // No inputs at all, yet an output claiming value -- can never balance.
+        let transaction = Transaction {
+            inputs: vec![],
+            outputs: vec![output(50, vec![0; 32])],
+        };
+
+        assert!(matches!(
+            pool.add(transaction, &HashSet::new()),
+            Err(MempoolError::AssetImbalance)
+        ));
+    }
+
+    #[test]
+    fn add_admits_a_balanced_transaction() {
+        let mut pool = Mempool::new();
+        let transaction = Transaction {
+            inputs: vec![],
+            outputs: vec![],
+        };
+
+        assert!(pool.add(transaction, &HashSet::new()).is_ok());
+        assert_eq!(pool.transactions().len(), 1);
+    }
+}
@@ -1,8 +1,33 @@
+use crate::signature::verify_signature;
+
 use super::*;
 
 pub struct Output {
     pub to_addr: Address,
     pub value: u64,
+    /// Which asset `value` is denominated in. `asset::native_asset()` for the chain's
+    /// built-in currency; any other id must have gone through an `IssuanceTransaction`.
+    pub asset_id: Hash,
+    /// Hash of the public key the spender must sign against to unlock this output.
+    pub locking_script: Hash,
+    /// Spender-supplied public key, whose hash must equal `locking_script`.
+    pub unlocking_pubkey: Vec<u8>,
+    /// Signature over the spending transaction's signature hash, proving the spender
+    /// holds the private key behind `unlocking_pubkey` for *this specific* spend --
+    /// unlike a bare preimage reveal, it can't be lifted into a competing transaction
+    /// that spends the same output differently. Ignored when the output is newly
+    /// created, and excluded from `bytes()` so it never affects the output's hash.
+    pub unlocking_signature: Vec<u8>,
+}
+
+impl Output {
+    /// Checks that the spender both owns `locking_script` (knows the public key it
+    /// commits to) and has signed `sighash` -- the spending transaction's own
+    /// inputs/outputs -- with the matching private key.
+    pub fn verify_script(&self, sighash: &Hash) -> bool {
+        PublicKey(&self.unlocking_pubkey).hash() == self.locking_script
+            && verify_signature(&self.unlocking_pubkey, sighash, &self.unlocking_signature)
+    }
 }
 
 impl Hashable for Output {
@@ -10,6 +35,16 @@ impl Hashable for Output {
         let mut bytes = vec![];
         bytes.extend(self.to_addr.as_bytes());
         bytes.extend(u64_bytes(&self.value));
+        bytes.extend(&self.asset_id);
+        bytes.extend(&self.locking_script);
         bytes
     }
 }
+
+struct PublicKey<'a>(&'a [u8]);
+
+impl Hashable for PublicKey<'_> {
+    fn bytes(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+}